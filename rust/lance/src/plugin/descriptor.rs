@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Manifest exported by every plugin library under one well-known symbol,
+//! modeled on GStreamer's `plugin_define!`: the host reads it to learn every
+//! entry a library bundles without constructing any of them.
+//!
+//! Despite `#[repr(C)]` on the records below, this is not a portable C ABI:
+//! [`PluginManifest::entries`] is a Rust slice and `create_plugin`/
+//! `destroy_plugin` traffic in `dyn PluginInstance` fat pointers, neither of
+//! which has a layout stable across rustc versions or other toolchains.
+//! `#[repr(C)]` only pins down the plain fields' layout within one build;
+//! the host and every plugin it `dlopen`s must still be compiled by the
+//! same rustc against the same `PluginInstance` definition, same as any
+//! other Rust plugin loaded through `libloading` rather than a real
+//! cross-language ABI.
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+use super::kind::PluginKind;
+use super::version::ApiVersion;
+use super::PluginInstance;
+
+/// Record describing one plugin entry within a library's [`PluginManifest`].
+/// All string fields are null-terminated and owned for the lifetime of the
+/// process (leaked once by [`PluginDescriptor::new`]); see the module docs
+/// for why `#[repr(C)]` here doesn't make this a stable C ABI.
+#[repr(C)]
+pub struct PluginDescriptor {
+    pub name: *const c_char,
+    pub version: *const c_char,
+    pub description: *const c_char,
+    pub kind: PluginKind,
+    pub api_version: ApiVersion,
+    pub create_plugin: unsafe extern "C" fn() -> *mut dyn PluginInstance,
+    pub destroy_plugin: unsafe extern "C" fn(*mut dyn PluginInstance),
+}
+
+// The descriptor is a plain, immutable, process-lifetime record; safe to
+// share across threads once published.
+unsafe impl Sync for PluginDescriptor {}
+
+impl PluginDescriptor {
+    /// Builds a descriptor, leaking `name`/`version`/`description` into
+    /// null-terminated `'static` C strings so the struct can be handed back
+    /// across the `dlopen` boundary as a `&'static` reference, without
+    /// tying its lifetime to any particular caller's stack frame.
+    pub fn new(
+        name: &str,
+        version: &str,
+        description: &str,
+        kind: PluginKind,
+        api_version: ApiVersion,
+        create_plugin: unsafe extern "C" fn() -> *mut dyn PluginInstance,
+        destroy_plugin: unsafe extern "C" fn(*mut dyn PluginInstance),
+    ) -> Self {
+        Self {
+            name: leak_cstring(name),
+            version: leak_cstring(version),
+            description: leak_cstring(description),
+            kind,
+            api_version,
+            create_plugin,
+            destroy_plugin,
+        }
+    }
+
+    /// Reads a `*const c_char` field of this descriptor back into an owned
+    /// `String`. # Safety: the pointer must come from [`PluginDescriptor::new`]
+    /// (or be otherwise valid, null-terminated UTF-8 for the process lifetime).
+    pub unsafe fn read_str(ptr: *const c_char) -> String {
+        std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+fn leak_cstring(s: &str) -> *const c_char {
+    CString::new(s)
+        .expect("plugin metadata must not contain interior NUL bytes")
+        .into_raw()
+}
+
+/// The full surface a library exports under `get_plugin_manifest`: every
+/// [`PluginDescriptor`] it bundles, e.g. a storage backend alongside a
+/// couple of scalar UDFs, each tagged with its own [`PluginKind`] and
+/// negotiated independently by [`super::PluginManager::load_plugin`]. See
+/// the module docs for why this is a same-rustc-build convention rather
+/// than a portable C ABI.
+#[repr(C)]
+pub struct PluginManifest {
+    pub entries: &'static [PluginDescriptor],
+}
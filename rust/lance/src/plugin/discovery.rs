@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Bootstraps a whole plugin folder from per-plugin manifests: TOML/JSON
+//! sidecar files declaring a plugin's `enabled` state, `config`, and its
+//! `dependencies` on other plugins, so [`PluginManager::discover`] can load
+//! them in the right order.
+//!
+//! [`PluginManager::discover`]: super::PluginManager::discover
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::PluginError;
+
+#[cfg(target_os = "linux")]
+const DYLIB_EXTENSION: &str = "so";
+#[cfg(target_os = "macos")]
+const DYLIB_EXTENSION: &str = "dylib";
+#[cfg(target_os = "windows")]
+const DYLIB_EXTENSION: &str = "dll";
+
+/// A per-plugin manifest (`<stem>.toml` or `<stem>.json`, alongside the
+/// library) describing how [`PluginManager::discover`](super::PluginManager::discover)
+/// should load it. `name` is only used to resolve `dependencies` between
+/// manifests; it need not match the name the loaded plugin itself reports.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifestFile {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub config: Value,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl PluginManifestFile {
+    /// The manifest assumed for a library with no sidecar manifest file:
+    /// enabled, unconfigured, and with no declared dependencies.
+    fn default_for(name: String) -> Self {
+        Self {
+            name,
+            version: None,
+            enabled: true,
+            config: Value::Null,
+            dependencies: Vec::new(),
+        }
+    }
+}
+
+/// One library found by [`scan`], paired with its (explicit or defaulted)
+/// manifest.
+pub struct DiscoveredPlugin {
+    pub library_path: PathBuf,
+    pub manifest: PluginManifestFile,
+}
+
+/// Finds every dynamic library in `dir`, loading each one's sidecar
+/// manifest (`<stem>.toml`/`.json`) if present, or assuming defaults
+/// otherwise. A library whose sidecar manifest fails to parse is logged
+/// and skipped rather than aborting the whole scan, mirroring the
+/// per-entry tolerance [`PluginManager::discover`](super::PluginManager::discover)
+/// applies to load failures.
+pub fn scan(dir: &Path) -> Result<Vec<DiscoveredPlugin>, PluginError> {
+    let mut discovered = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(PluginError::Io)? {
+        let path = entry.map_err(PluginError::Io)?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(DYLIB_EXTENSION) {
+            continue;
+        }
+
+        let stem = manifest_stem(&path);
+        let manifest = match read_manifest(dir, &stem) {
+            Ok(manifest) => manifest.unwrap_or_else(|| PluginManifestFile::default_for(stem)),
+            Err(e) => {
+                log::warn!("Skipping plugin at {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        discovered.push(DiscoveredPlugin {
+            library_path: path,
+            manifest,
+        });
+    }
+
+    Ok(discovered)
+}
+
+/// Strips the platform library prefix (`lib` on Unix) and extension from a
+/// library's file name, e.g. `libfoo.so` -> `foo`.
+fn manifest_stem(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    #[cfg(not(target_os = "windows"))]
+    let stem = stem.strip_prefix("lib").map(str::to_string).unwrap_or(stem);
+
+    stem
+}
+
+/// Reads `<stem>.toml` or, failing that, `<stem>.json` from `dir`. Returns
+/// `Ok(None)` if neither exists.
+fn read_manifest(dir: &Path, stem: &str) -> Result<Option<PluginManifestFile>, PluginError> {
+    let toml_path = dir.join(format!("{stem}.toml"));
+    if toml_path.exists() {
+        let contents = fs::read_to_string(&toml_path).map_err(PluginError::Io)?;
+        return toml::from_str(&contents).map(Some).map_err(|e| {
+            PluginError::Registry(format!("invalid manifest {}: {}", toml_path.display(), e))
+        });
+    }
+
+    let json_path = dir.join(format!("{stem}.json"));
+    if json_path.exists() {
+        let contents = fs::read_to_string(&json_path).map_err(PluginError::Io)?;
+        return serde_json::from_str(&contents).map(Some).map_err(|e| {
+            PluginError::Registry(format!("invalid manifest {}: {}", json_path.display(), e))
+        });
+    }
+
+    Ok(None)
+}
+
+/// Orders `plugins` so every dependency loads before its dependents, via a
+/// depth-first topological sort. Fails with
+/// [`PluginError::DependencyCycle`] if the dependency graph isn't a DAG.
+/// Dependencies naming a plugin absent from `plugins` are ignored.
+pub fn topological_order(
+    plugins: Vec<DiscoveredPlugin>,
+) -> Result<Vec<DiscoveredPlugin>, PluginError> {
+    let by_name: HashMap<String, usize> = plugins
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.manifest.name.clone(), i))
+        .collect();
+
+    let mut visited = vec![false; plugins.len()];
+    let mut on_stack = vec![false; plugins.len()];
+    let mut order = Vec::with_capacity(plugins.len());
+
+    for start in 0..plugins.len() {
+        if !visited[start] {
+            visit(
+                start,
+                &plugins,
+                &by_name,
+                &mut visited,
+                &mut on_stack,
+                &mut order,
+            )?;
+        }
+    }
+
+    let mut by_index: Vec<Option<DiscoveredPlugin>> = plugins.into_iter().map(Some).collect();
+    Ok(order
+        .into_iter()
+        .map(|i| by_index[i].take().expect("each index visited exactly once"))
+        .collect())
+}
+
+fn visit(
+    index: usize,
+    plugins: &[DiscoveredPlugin],
+    by_name: &HashMap<String, usize>,
+    visited: &mut [bool],
+    on_stack: &mut [bool],
+    order: &mut Vec<usize>,
+) -> Result<(), PluginError> {
+    if on_stack[index] {
+        return Err(PluginError::DependencyCycle(
+            plugins[index].manifest.name.clone(),
+        ));
+    }
+    if visited[index] {
+        return Ok(());
+    }
+
+    on_stack[index] = true;
+    for dep in &plugins[index].manifest.dependencies {
+        if let Some(&dep_index) = by_name.get(dep) {
+            visit(dep_index, plugins, by_name, visited, on_stack, order)?;
+        }
+    }
+    on_stack[index] = false;
+    visited[index] = true;
+    order.push(index);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// A fresh scratch directory under the OS temp dir, unique to this test
+    /// process and `tag` so parallel test runs don't collide.
+    fn scratch_dir(tag: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("lance-plugin-scan-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn scan_reads_toml_and_json_manifests_and_honors_enabled_and_dependencies() {
+        let dir = scratch_dir("toml_json");
+
+        fs::write(dir.join(format!("libtoml_plugin.{DYLIB_EXTENSION}")), b"").unwrap();
+        fs::write(
+            dir.join("toml_plugin.toml"),
+            "name = \"toml_plugin\"\nenabled = false\ndependencies = [\"json_plugin\"]\n",
+        )
+        .unwrap();
+
+        fs::write(dir.join(format!("libjson_plugin.{DYLIB_EXTENSION}")), b"").unwrap();
+        fs::write(dir.join("json_plugin.json"), r#"{"name": "json_plugin"}"#).unwrap();
+
+        let mut discovered = scan(&dir).unwrap();
+        discovered.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+
+        assert_eq!(discovered.len(), 2);
+        assert_eq!(discovered[0].manifest.name, "json_plugin");
+        assert!(discovered[0].manifest.enabled);
+        assert_eq!(discovered[1].manifest.name, "toml_plugin");
+        assert!(!discovered[1].manifest.enabled);
+        assert_eq!(discovered[1].manifest.dependencies, vec!["json_plugin"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_defaults_manifest_for_library_with_no_sidecar() {
+        let dir = scratch_dir("no_sidecar");
+        fs::write(dir.join(format!("libbare.{DYLIB_EXTENSION}")), b"").unwrap();
+
+        let discovered = scan(&dir).unwrap();
+
+        assert_eq!(discovered.len(), 1);
+        assert_eq!(discovered[0].manifest.name, "bare");
+        assert!(discovered[0].manifest.enabled);
+        assert!(discovered[0].manifest.dependencies.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn scan_skips_library_with_malformed_manifest_but_keeps_others() {
+        let dir = scratch_dir("malformed");
+
+        fs::write(dir.join(format!("libgood.{DYLIB_EXTENSION}")), b"").unwrap();
+        fs::write(dir.join("good.toml"), "name = \"good\"\n").unwrap();
+
+        fs::write(dir.join(format!("libbad.{DYLIB_EXTENSION}")), b"").unwrap();
+        fs::write(dir.join("bad.toml"), "not valid toml {{{").unwrap();
+
+        let discovered =
+            scan(&dir).expect("a malformed sidecar manifest should not fail the whole scan");
+
+        assert_eq!(discovered.len(), 1, "the well-formed library should still be discovered");
+        assert_eq!(discovered[0].manifest.name, "good");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn plugin(name: &str, dependencies: &[&str]) -> DiscoveredPlugin {
+        DiscoveredPlugin {
+            library_path: PathBuf::from(format!("{name}.so")),
+            manifest: PluginManifestFile {
+                name: name.to_string(),
+                version: None,
+                enabled: true,
+                config: Value::Null,
+                dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn dependencies_load_before_dependents() {
+        let plugins = vec![plugin("a", &["b"]), plugin("b", &["c"]), plugin("c", &[])];
+        let order = topological_order(plugins).unwrap();
+        let names: Vec<&str> = order.iter().map(|p| p.manifest.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn missing_dependency_is_ignored() {
+        let plugins = vec![plugin("a", &["nonexistent"])];
+        let order = topological_order(plugins).unwrap();
+        assert_eq!(order.len(), 1);
+    }
+
+    #[test]
+    fn cycle_is_rejected() {
+        let plugins = vec![plugin("a", &["b"]), plugin("b", &["a"])];
+        let err = topological_order(plugins).unwrap_err();
+        assert!(matches!(err, PluginError::DependencyCycle(_)));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn manifest_stem_strips_lib_prefix_and_extension() {
+        assert_eq!(manifest_stem(Path::new("/plugins/libfoo.so")), "foo");
+    }
+}
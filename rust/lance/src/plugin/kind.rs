@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! What a loaded plugin entry is *for*. Lets one shared library bundle
+//! several distinct extension points -- e.g. a storage backend alongside a
+//! couple of scalar UDFs -- each negotiated against its own host API
+//! version; see [`PluginManager::plugins_of_kind`](super::PluginManager::plugins_of_kind).
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default)]
+pub enum PluginKind {
+    #[default]
+    Udf,
+    StorageBackend,
+    IndexProvider,
+    ScanTransform,
+}
+
+impl fmt::Display for PluginKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            PluginKind::Udf => "udf",
+            PluginKind::StorageBackend => "storage_backend",
+            PluginKind::IndexProvider => "index_provider",
+            PluginKind::ScanTransform => "scan_transform",
+        };
+        write!(f, "{s}")
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Declares the exported plugin surface for a plugin crate so authors never
+//! have to hand-write `#[no_mangle] extern "C"` functions or touch raw
+//! pointers. Like the [`PluginDescriptor`] it builds, this is a
+//! same-rustc-build convention, not a portable C ABI.
+//!
+//! [`PluginDescriptor`]: crate::plugin::PluginDescriptor
+
+use super::PluginInstance;
+
+/// Shared `create_plugin` body for every [`declare_plugin!`] entry. Generic
+/// over the plugin type rather than generated per-entry, so entries don't
+/// need distinctly-named `extern "C"` functions.
+#[doc(hidden)]
+pub unsafe extern "C" fn create_instance<T>() -> *mut dyn PluginInstance
+where
+    T: PluginInstance + Default + 'static,
+{
+    Box::into_raw(Box::new(T::default()))
+}
+
+/// Shared `destroy_plugin` body: dropping a boxed trait object needs no
+/// knowledge of the concrete type, so one instance serves every entry.
+#[doc(hidden)]
+pub unsafe extern "C" fn destroy_instance(plugin: *mut dyn PluginInstance) {
+    drop(Box::from_raw(plugin));
+}
+
+/// Expands to a single exported `get_plugin_manifest` extern "C" function
+/// publishing one [`PluginDescriptor`] per `name, version, description,
+/// kind, api_version => Type` entry. The descriptor strings are taken
+/// verbatim from the macro invocation rather than read off a constructed
+/// instance, so `PluginManager::load_plugin` can learn a library's contents
+/// without building any of its plugins first.
+///
+/// Each entry states its own `api_version`, checked against
+/// [`host_version_for`](crate::plugin)'s per-[`PluginKind`] host version --
+/// so a library bundling a `StorageBackend` built against an old storage
+/// ABI alongside a current `Udf` gets the storage entry rejected (or
+/// adapter-bridged) while the UDF still loads.
+///
+/// ```ignore
+/// declare_plugin!(
+///     "my_plugin", "1.0", "Does a thing", PluginKind::Udf, CURRENT_API_VERSION => MyPlugin
+/// );
+/// declare_plugin!(
+///     "uppercase", "1.0", "Uppercases input", PluginKind::Udf, CURRENT_API_VERSION => Uppercase,
+///     "reverse", "1.0", "Reverses input", PluginKind::Udf, CURRENT_API_VERSION => Reverse,
+/// );
+/// ```
+///
+/// [`PluginDescriptor`]: crate::plugin::PluginDescriptor
+#[macro_export]
+macro_rules! declare_plugin {
+    ($($name:expr, $version:expr, $description:expr, $kind:expr, $api_version:expr => $plugin_type:ty),+ $(,)?) => {
+        #[no_mangle]
+        pub extern "C" fn get_plugin_manifest() -> &'static $crate::plugin::PluginManifest {
+            static ENTRIES: ::std::sync::OnceLock<
+                ::std::vec::Vec<$crate::plugin::PluginDescriptor>,
+            > = ::std::sync::OnceLock::new();
+            static MANIFEST: ::std::sync::OnceLock<$crate::plugin::PluginManifest> =
+                ::std::sync::OnceLock::new();
+
+            let entries = ENTRIES.get_or_init(|| {
+                ::std::vec![
+                    $(
+                        $crate::plugin::PluginDescriptor::new(
+                            $name,
+                            $version,
+                            $description,
+                            $kind,
+                            $api_version,
+                            $crate::plugin::create_instance::<$plugin_type>,
+                            $crate::plugin::destroy_instance,
+                        )
+                    ),+
+                ]
+            });
+
+            MANIFEST.get_or_init(|| $crate::plugin::PluginManifest { entries })
+        }
+    };
+}
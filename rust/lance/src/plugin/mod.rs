@@ -0,0 +1,1022 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use arrow_array::RecordBatch;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::prelude::SessionContext;
+use libloading::{Library, Symbol};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use serde_json::Value;
+use std::fmt;
+
+mod descriptor;
+mod discovery;
+mod kind;
+mod macros;
+mod registry;
+mod udf;
+mod version;
+
+pub use descriptor::{PluginDescriptor, PluginManifest};
+pub use kind::PluginKind;
+pub use macros::{create_instance, destroy_instance};
+pub use registry::{PluginRegistry, RegistryEntry};
+pub use udf::PluginSignature;
+pub use version::{AdapterFn, ApiVersion};
+
+/// The host's own API version. A plugin loads if its major matches and its
+/// minor is no newer than this; see [`ApiVersion::is_compatible_with`].
+pub const CURRENT_API_VERSION: ApiVersion = ApiVersion::new(1, 0);
+
+/// The host's current API version for [`PluginKind::StorageBackend`]
+/// specifically. Bumped independently of [`CURRENT_API_VERSION`] because
+/// the storage ABI and the scalar/aggregate UDF ABI evolve on their own
+/// schedules; see [`host_version_for`].
+pub const STORAGE_BACKEND_API_VERSION: ApiVersion = ApiVersion::new(2, 0);
+
+/// Default location of the persistent plugin registry cache. Relative to
+/// the process's current directory, matching how `load_plugin` accepts
+/// relative library paths.
+pub const DEFAULT_REGISTRY_PATH: &str = "plugins.msgpackz";
+
+/// The host's current API version for each [`PluginKind`], mirroring
+/// ScummVM's `pluginTypeVersions`: bumping one kind's ABI (say, the storage
+/// backend contract) doesn't force reloading plugins of a different kind
+/// from the same library. [`PluginKind::StorageBackend`] tracks its own,
+/// independently-versioned constant; the rest currently share the host's
+/// general-purpose [`CURRENT_API_VERSION`] until they, too, need to diverge.
+fn host_version_for(kind: PluginKind) -> ApiVersion {
+    match kind {
+        PluginKind::Udf => CURRENT_API_VERSION,
+        PluginKind::StorageBackend => STORAGE_BACKEND_API_VERSION,
+        PluginKind::IndexProvider => CURRENT_API_VERSION,
+        PluginKind::ScanTransform => CURRENT_API_VERSION,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginMetadata {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    /// What this plugin entry is for. Set authoritatively from the
+    /// descriptor at load time, same as [`negotiated_version`].
+    ///
+    /// [`negotiated_version`]: PluginMetadata::negotiated_version
+    #[serde(default)]
+    pub kind: PluginKind,
+    /// API version this plugin was actually loaded under, after
+    /// negotiation (may be older than [`CURRENT_API_VERSION`]).
+    #[serde(default)]
+    pub negotiated_version: ApiVersion,
+}
+
+pub trait PluginInstance {
+    fn init(&mut self, config: &Value) -> Result<(), String>;
+    fn execute(&self, input: &str) -> String;
+
+    /// Argument/return types this plugin exposes to query execution.
+    /// Defaults to a single `Utf8 -> Utf8` signature matching [`execute`];
+    /// Arrow-native plugins override this alongside [`execute_batch`].
+    ///
+    /// [`execute`]: PluginInstance::execute
+    /// [`execute_batch`]: PluginInstance::execute_batch
+    fn signature(&self) -> PluginSignature {
+        PluginSignature {
+            args: vec![DataType::Utf8],
+            return_type: DataType::Utf8,
+        }
+    }
+
+    /// Arrow-native counterpart to [`execute`](PluginInstance::execute), used
+    /// when the plugin is registered as a DataFusion UDF via
+    /// [`PluginManager::register_udfs`]. The default delegates row-by-row
+    /// through `execute`, assuming a single `Utf8` column in and out.
+    fn execute_batch(&self, input: &RecordBatch) -> Result<RecordBatch, PluginError> {
+        udf::default_execute_batch(self, input)
+    }
+}
+
+/// How to tear down a [`LoadedPlugin::instance`]. A natively-negotiated
+/// entry was allocated by the plugin library's own `create_plugin`, so it
+/// must be freed through that same library's `destroy_plugin` -- but an
+/// [`AdapterFn`]-bridged entry returns a `Box` the *host* allocated (it
+/// wraps an older-major descriptor behind the current trait; see
+/// [`AdapterFn`]), which must instead be dropped as a plain
+/// Rust `Box`. Freeing a host-allocated `Box` through the plugin's
+/// `destroy_plugin`, or vice versa, is an allocator mismatch.
+enum PluginDestroy {
+    Native(unsafe extern "C" fn(*mut dyn PluginInstance)),
+    Host,
+}
+
+/// One loaded plugin entry: its instance, a handle keeping its (possibly
+/// shared) library alive, the metadata it was registered under, and how to
+/// tear it down.
+struct LoadedPlugin {
+    instance: Box<dyn PluginInstance>,
+    /// Kept alive only so the library isn't `dlclose`'d while any of its
+    /// entries are still loaded; never read directly.
+    #[allow(dead_code)]
+    library: Arc<Library>,
+    metadata: PluginMetadata,
+    destroy: PluginDestroy,
+}
+
+impl LoadedPlugin {
+    /// Tears down this entry's instance via whichever allocator produced it
+    /// -- the plugin library's `destroy_plugin` for a natively-negotiated
+    /// entry, or a plain Rust `drop` for an adapter-bridged, host-allocated
+    /// one. See [`PluginDestroy`].
+    fn teardown(self) {
+        match self.destroy {
+            PluginDestroy::Native(destroy) => unsafe {
+                destroy(Box::into_raw(self.instance));
+            },
+            PluginDestroy::Host => drop(self.instance),
+        }
+    }
+}
+
+pub struct PluginManager {
+    plugins: HashMap<(PluginKind, String), LoadedPlugin>,
+    registry: PluginRegistry,
+    /// Adapters bridging one-major-behind plugins, keyed by the kind and
+    /// (old) major version they bridge.
+    adapters: HashMap<(PluginKind, u32), AdapterFn>,
+    /// Names of [`PluginKind::Udf`] entries last handed to
+    /// [`register_udfs`](Self::register_udfs) as a raw pointer a
+    /// `SessionContext` may still be holding. Unloading or reloading one of
+    /// these is refused until [`acknowledge_udfs_dropped`](Self::acknowledge_udfs_dropped)
+    /// confirms no live `SessionContext` references it anymore.
+    registered_udfs: HashSet<String>,
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    LibraryLoad(libloading::Error),
+    SymbolError(libloading::Error),
+    IncompatibleAPI {
+        kind: PluginKind,
+        plugin_version: ApiVersion,
+        host_version: ApiVersion,
+    },
+    NotFound,
+    Arrow(arrow_schema::ArrowError),
+    Unsupported(String),
+    Io(std::io::Error),
+    Registry(String),
+    /// A manifest dependency graph passed to [`PluginManager::discover`]
+    /// contains a cycle reachable from the named plugin.
+    DependencyCycle(String),
+    /// Refused to unload or reload a [`PluginKind::Udf`] entry that may
+    /// still be referenced by a raw pointer inside a live `SessionContext`;
+    /// see [`PluginManager::register_udfs`].
+    UdfStillRegistered(String),
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginError::LibraryLoad(e) => write!(f, "Library load error: {}", e),
+            PluginError::SymbolError(e) => write!(f, "Symbol error: {}", e),
+            PluginError::IncompatibleAPI {
+                kind,
+                plugin_version,
+                host_version,
+            } => write!(
+                f,
+                "Incompatible API version for {} plugin: plugin is v{}, host is v{}",
+                kind, plugin_version, host_version
+            ),
+            PluginError::NotFound => write!(f, "Plugin not found"),
+            PluginError::Arrow(e) => write!(f, "Arrow error: {}", e),
+            PluginError::Unsupported(msg) => write!(f, "Unsupported: {}", msg),
+            PluginError::Io(e) => write!(f, "I/O error: {}", e),
+            PluginError::Registry(msg) => write!(f, "Registry error: {}", msg),
+            PluginError::DependencyCycle(name) => write!(
+                f,
+                "Plugin dependency cycle detected while resolving '{}'",
+                name
+            ),
+            PluginError::UdfStillRegistered(name) => write!(
+                f,
+                "Plugin '{}' is still registered as a DataFusion UDF; call \
+                 PluginManager::acknowledge_udfs_dropped after dropping every \
+                 SessionContext that holds it before unloading or reloading",
+                name
+            ),
+        }
+    }
+}
+
+impl PluginManager {
+    /// Opens (or creates) the registry at [`DEFAULT_REGISTRY_PATH`].
+    pub fn new() -> Result<Self, PluginError> {
+        Self::with_registry_path(DEFAULT_REGISTRY_PATH)
+    }
+
+    /// Opens (or creates) the persistent registry cache at `path`. Entries
+    /// that fail to decode are logged and skipped rather than failing the
+    /// whole open.
+    pub fn with_registry_path(path: impl Into<PathBuf>) -> Result<Self, PluginError> {
+        let (registry, errors) = PluginRegistry::open(path)?;
+        for err in errors {
+            log::warn!(
+                "Dropping corrupt plugin registry entry '{}': {}",
+                err.name,
+                err.message
+            );
+        }
+        Ok(Self {
+            plugins: HashMap::new(),
+            registry,
+            adapters: HashMap::new(),
+            registered_udfs: HashSet::new(),
+        })
+    }
+
+    /// Registers an adapter that bridges `kind` plugins built against major
+    /// version `old_major` (which must be exactly one behind that kind's
+    /// current host version) into the current [`PluginInstance`] trait.
+    pub fn register_adapter(&mut self, kind: PluginKind, old_major: u32, adapter: AdapterFn) {
+        self.adapters.insert((kind, old_major), adapter);
+    }
+
+    /// The negotiated API version of every currently loaded plugin, keyed
+    /// by its own `(kind, name)` -- the same key [`PluginManager`] stores it
+    /// under -- so two kinds sharing a name don't collide into one entry.
+    pub fn compatible_versions(&self) -> HashMap<(PluginKind, String), ApiVersion> {
+        self.plugins
+            .iter()
+            .map(|(key, p)| (key.clone(), p.metadata.negotiated_version))
+            .collect()
+    }
+
+    /// Metadata for every currently loaded plugin of `kind`.
+    pub fn plugins_of_kind(&self, kind: PluginKind) -> Vec<PluginMetadata> {
+        self.plugins
+            .values()
+            .filter(|p| p.metadata.kind == kind)
+            .map(|p| p.metadata.clone())
+            .collect()
+    }
+
+    /// Runs the `name` plugin of `kind`, disambiguating libraries that
+    /// bundle more than one entry under the same name (e.g. a UDF and a
+    /// storage backend both called `"custom"`).
+    pub fn execute_for_kind(
+        &self,
+        kind: PluginKind,
+        name: &str,
+        input: &str,
+    ) -> Result<String, String> {
+        self.plugins
+            .get(&(kind, name.to_string()))
+            .map(|p| p.instance.execute(input))
+            .ok_or_else(|| format!("{} plugin '{}' not found", kind, name))
+    }
+
+    /// Loads the plugin entries a library's [`PluginManifest`] publishes,
+    /// sharing one `dlopen`'d handle across all of them (see
+    /// [`load_plugin_with_config`](Self::load_plugin_with_config) for how a
+    /// per-entry version mismatch is handled). Each entry is initialized
+    /// with an empty config; use [`discover`](Self::discover) to load from a
+    /// manifest folder that supplies one.
+    pub fn load_plugin(&mut self, path: &Path) -> Result<(), PluginError> {
+        self.load_plugin_with_config(path, &Value::Null)
+    }
+
+    /// Bootstraps every plugin in `dir`: scans it for the platform's
+    /// dynamic library extension, reads each library's optional
+    /// `<stem>.toml`/`.json` sidecar manifest (`name`, `version`,
+    /// `enabled`, `config`, `dependencies`), skips `enabled = false`
+    /// entries, and loads the rest in dependency order. A library that
+    /// fails to load is logged and skipped rather than aborting the whole
+    /// folder, mirroring the per-entry tolerance
+    /// [`load_plugin_with_config`](Self::load_plugin_with_config) applies
+    /// within one library.
+    pub fn discover(&mut self, dir: &Path) -> Result<(), PluginError> {
+        let discovered = discovery::scan(dir)?;
+        let ordered = discovery::topological_order(discovered)?;
+
+        for plugin in ordered {
+            if !plugin.manifest.enabled {
+                log::info!("Skipping disabled plugin '{}'", plugin.manifest.name);
+                continue;
+            }
+            if let Err(e) =
+                self.load_plugin_with_config(&plugin.library_path, &plugin.manifest.config)
+            {
+                log::warn!(
+                    "Skipping plugin '{}' ({}): {}",
+                    plugin.manifest.name,
+                    plugin.library_path.display(),
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads as many of the library's manifest entries as negotiate
+    /// successfully. A library bundling several [`PluginKind`]s (say, a
+    /// storage backend alongside a UDF) may have one entry rejected for an
+    /// incompatible API version while the rest still load: each entry's
+    /// failure is logged and skipped rather than aborting its siblings.
+    /// Fails only if every entry in the manifest failed to load.
+    fn load_plugin_with_config(&mut self, path: &Path, config: &Value) -> Result<(), PluginError> {
+        unsafe {
+            log::debug!("Loading plugin library: {}", path.display());
+
+            let lib = Arc::new(Library::new(path).map_err(PluginError::LibraryLoad)?);
+
+            let manifest_fn: Symbol<unsafe extern "C" fn() -> &'static PluginManifest> = lib
+                .get(b"get_plugin_manifest")
+                .map_err(PluginError::SymbolError)?;
+            let manifest = manifest_fn();
+
+            let mut loaded_any = false;
+            let mut last_err = None;
+            for descriptor in manifest.entries {
+                match self.load_entry(descriptor, &lib, path, config) {
+                    Ok(()) => loaded_any = true,
+                    Err(e) => {
+                        log::warn!(
+                            "Skipping {} entry in {}: {}",
+                            descriptor.kind,
+                            path.display(),
+                            e
+                        );
+                        last_err = Some(e);
+                    }
+                }
+            }
+
+            match last_err {
+                Some(e) if !loaded_any => Err(e),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Negotiates and constructs a single [`PluginDescriptor`] entry from an
+    /// already-opened library.
+    unsafe fn load_entry(
+        &mut self,
+        descriptor: &PluginDescriptor,
+        lib: &Arc<Library>,
+        path: &Path,
+        config: &Value,
+    ) -> Result<(), PluginError> {
+        let name = PluginDescriptor::read_str(descriptor.name);
+        if descriptor.kind == PluginKind::Udf && self.registered_udfs.contains(&name) {
+            return Err(PluginError::UdfStillRegistered(name));
+        }
+
+        let plugin_version = descriptor.api_version;
+        let host_version = host_version_for(descriptor.kind);
+
+        let (mut plugin, destroy) = if plugin_version.is_compatible_with(host_version) {
+            (
+                Box::from_raw((descriptor.create_plugin)()),
+                PluginDestroy::Native(descriptor.destroy_plugin),
+            )
+        } else if plugin_version.is_one_major_behind(host_version) {
+            let adapter = self
+                .adapters
+                .get(&(descriptor.kind, plugin_version.major))
+                .ok_or(PluginError::IncompatibleAPI {
+                    kind: descriptor.kind,
+                    plugin_version,
+                    host_version,
+                })?;
+            (adapter(descriptor), PluginDestroy::Host)
+        } else {
+            return Err(PluginError::IncompatibleAPI {
+                kind: descriptor.kind,
+                plugin_version,
+                host_version,
+            });
+        };
+
+        let metadata = PluginMetadata {
+            name,
+            version: PluginDescriptor::read_str(descriptor.version),
+            description: PluginDescriptor::read_str(descriptor.description),
+            kind: descriptor.kind,
+            negotiated_version: plugin_version,
+        };
+        log::debug!(
+            "Loaded {} plugin '{}' v{} (API v{}, negotiated against host v{})",
+            descriptor.kind,
+            metadata.name,
+            metadata.version,
+            plugin_version,
+            host_version
+        );
+
+        plugin
+            .init(config)
+            .map_err(|_| PluginError::SymbolError(libloading::Error::DlSymUnknown))?;
+
+        let signature = plugin.signature();
+        self.registry.add(RegistryEntry {
+            metadata: metadata.clone(),
+            library_path: path.to_path_buf(),
+            signature: (&signature).into(),
+        })?;
+
+        let key = (descriptor.kind, metadata.name.clone());
+        if let Some(previous) = self.plugins.remove(&key) {
+            // Tear down via whichever allocator produced the previous
+            // instance, not the implicit `Drop` a plain `insert` overwrite
+            // would trigger: see `PluginDestroy`.
+            previous.teardown();
+        }
+
+        self.plugins.insert(
+            key,
+            LoadedPlugin {
+                instance: plugin,
+                library: Arc::clone(lib),
+                metadata,
+                destroy,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Unloads the loaded plugin named `name` with the lowest [`PluginKind`],
+    /// regardless of kind. Ambiguous when a library bundles more than one
+    /// entry under the same name (e.g. a UDF and a storage backend both
+    /// called `"custom"`) -- use [`unload_for_kind`](Self::unload_for_kind)
+    /// to unload a specific one instead of whichever sorts lowest.
+    pub fn unload_plugin(&mut self, name: &str) -> Result<(), PluginError> {
+        let key = self
+            .plugins
+            .keys()
+            .filter(|(_, n)| n == name)
+            .min_by_key(|(kind, _)| *kind)
+            .cloned()
+            .ok_or(PluginError::NotFound)?;
+        self.unload_key(key)
+    }
+
+    /// Unloads the `name` plugin of `kind` specifically, disambiguating
+    /// libraries that bundle more than one entry under the same name. See
+    /// [`execute_for_kind`](Self::execute_for_kind) for the read-path
+    /// counterpart.
+    pub fn unload_for_kind(&mut self, kind: PluginKind, name: &str) -> Result<(), PluginError> {
+        let key = (kind, name.to_string());
+        if !self.plugins.contains_key(&key) {
+            return Err(PluginError::NotFound);
+        }
+        self.unload_key(key)
+    }
+
+    fn unload_key(&mut self, key: (PluginKind, String)) -> Result<(), PluginError> {
+        if key.0 == PluginKind::Udf && self.registered_udfs.contains(&key.1) {
+            return Err(PluginError::UdfStillRegistered(key.1));
+        }
+
+        let plugin = self.plugins.remove(&key).expect("key just found above");
+        plugin.teardown();
+
+        self.registry.remove(key.0, &key.1)?;
+
+        Ok(())
+    }
+
+    /// Registers `entry` in the persistent cache without loading its
+    /// library. Useful for pre-declaring plugins that will be loaded later.
+    pub fn add(&mut self, entry: RegistryEntry) -> Result<(), PluginError> {
+        self.registry.add(entry)
+    }
+
+    /// Removes the `(kind, name)` entry from the persistent cache without
+    /// touching any currently-loaded library.
+    pub fn remove(&mut self, kind: PluginKind, name: &str) -> Result<(), PluginError> {
+        self.registry.remove(kind, name)
+    }
+
+    /// Lists cached plugin metadata without `dlopen`-ing anything.
+    pub fn list_registered(&self) -> Vec<RegistryEntry> {
+        self.registry.list_registered()
+    }
+
+    /// Runs the loaded plugin named `name` with the lowest [`PluginKind`]
+    /// (see its declaration order), regardless of kind. Use
+    /// [`execute_for_kind`](Self::execute_for_kind) when a library bundles
+    /// more than one entry under the same name.
+    pub fn execute_plugin(&self, name: &str, input: &str) -> Result<String, String> {
+        self.plugins
+            .iter()
+            .filter(|((_, n), _)| n == name)
+            .min_by_key(|((kind, _), _)| *kind)
+            .map(|(_, p)| p.instance.execute(input))
+            .ok_or_else(|| format!("Plugin {} not found", name))
+    }
+
+    /// Returns the negotiated metadata of the loaded plugin named `name`
+    /// with the lowest [`PluginKind`], recorded at load time. Ambiguous
+    /// when a library bundles more than one entry under the same name; use
+    /// [`get_metadata_for_kind`](Self::get_metadata_for_kind) to ask for a
+    /// specific one instead of whichever sorts lowest.
+    pub fn get_metadata(&self, name: &str) -> Option<PluginMetadata> {
+        self.plugins
+            .iter()
+            .filter(|((_, n), _)| n == name)
+            .min_by_key(|((kind, _), _)| *kind)
+            .map(|(_, p)| p.metadata.clone())
+    }
+
+    /// Returns the negotiated metadata of the `name` plugin of `kind`
+    /// specifically, disambiguating libraries that bundle more than one
+    /// entry under the same name.
+    pub fn get_metadata_for_kind(&self, kind: PluginKind, name: &str) -> Option<PluginMetadata> {
+        self.plugins
+            .get(&(kind, name.to_string()))
+            .map(|p| p.metadata.clone())
+    }
+
+    /// Registers every loaded [`PluginKind::Udf`] entry as a DataFusion
+    /// scalar UDF (keyed by plugin name) on `ctx`, so queries can call
+    /// plugins as expressions. Other kinds (e.g. `StorageBackend`) aren't
+    /// expressions callable from SQL and are skipped.
+    ///
+    /// Marks each registered name so [`unload_plugin`](Self::unload_plugin),
+    /// [`unload_for_kind`](Self::unload_for_kind), and reloading the same
+    /// entry are refused with [`PluginError::UdfStillRegistered`] until
+    /// [`acknowledge_udfs_dropped`](Self::acknowledge_udfs_dropped) confirms
+    /// no live `SessionContext` still references it -- the UDF closure holds
+    /// a raw pointer into the plugin's boxed instance, so tearing it down
+    /// underneath a registered UDF would leave that pointer dangling.
+    pub fn register_udfs(&mut self, ctx: &SessionContext) {
+        udf::register_all(
+            self.plugins
+                .iter()
+                .filter(|((kind, _), _)| *kind == PluginKind::Udf)
+                .map(|((_, name), p)| (name.clone(), p.instance.as_ref() as *const dyn PluginInstance)),
+            ctx,
+        );
+        self.registered_udfs.extend(
+            self.plugins
+                .keys()
+                .filter(|(kind, _)| *kind == PluginKind::Udf)
+                .map(|(_, name)| name.clone()),
+        );
+    }
+
+    /// Confirms that every `SessionContext` [`register_udfs`](Self::register_udfs)
+    /// registered UDFs on has since been dropped (or had those UDFs
+    /// otherwise discarded), clearing the bookkeeping that refuses unload
+    /// and reload for those plugin names. The caller is responsible for the
+    /// confirmation being true -- `PluginManager` has no way to observe a
+    /// `SessionContext`'s lifetime itself.
+    pub fn acknowledge_udfs_dropped(&mut self) {
+        self.registered_udfs.clear();
+    }
+}
+
+impl Drop for PluginManager {
+    fn drop(&mut self) {
+        let plugins = std::mem::take(&mut self.plugins);
+        for ((kind, name), plugin) in plugins.into_iter() {
+            log::debug!("Dropping {} plugin: {}", kind, name);
+            plugin.teardown();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+    use std::sync::{Once, OnceLock};
+
+    static INIT: Once = Once::new();
+    static PLUGIN_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+    fn init_logger() {
+        INIT.call_once(|| {
+            env_logger::builder()
+                .filter_level(log::LevelFilter::Debug)
+                .init();
+        });
+    }
+
+    /// Resolves the workspace `target/` directory the way `cargo test`
+    /// itself would: honor `CARGO_TARGET_DIR` if the environment overrides
+    /// it, otherwise fall back to the default location relative to this
+    /// crate's manifest, so the integration tests below find the example
+    /// plugin's `.so`/`.dylib`/`.dll` on any checkout, not just one machine.
+    fn target_dir() -> PathBuf {
+        std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../target")))
+    }
+
+    fn get_plugin_path() -> &'static Path {
+        PLUGIN_PATH.get_or_init(|| {
+            let profile = if cfg!(debug_assertions) { "debug" } else { "release" };
+            let mut path = target_dir().join(profile).join("examples");
+
+            #[cfg(target_os = "linux")]
+            path.push("libtest_plugin.so");
+            #[cfg(target_os = "macos")]
+            path.push("libtest_plugin.dylib");
+            #[cfg(target_os = "windows")]
+            path.push("test_plugin.dll");
+
+            assert!(path.exists(), "Plugin not found at: {}", path.display());
+            path
+        })
+    }
+
+    /// A manager backed by a unique, per-test registry file so concurrent
+    /// tests don't race on `DEFAULT_REGISTRY_PATH`.
+    fn test_manager(tag: &str) -> PluginManager {
+        let path = env::temp_dir().join(format!(
+            "lance-plugin-registry-{tag}-{}.msgpackz",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        PluginManager::with_registry_path(path).expect("open registry")
+    }
+
+    #[test]
+    fn test_load_valid_plugin() {
+        init_logger();
+        let mut manager = test_manager("load_valid");
+        let path = get_plugin_path();
+
+        let result = manager.load_plugin(path);
+        assert!(result.is_ok(), "Load failed: {:?}", result.err());
+
+        let metadata = manager.get_metadata("test_plugin").unwrap();
+        assert_eq!(metadata.version, "1.0");
+    }
+
+    #[test]
+    fn test_load_nonexistent_library() {
+        let mut manager = test_manager("load_nonexistent");
+        let path = Path::new("non_existent_plugin.so");
+
+        let result = manager.load_plugin(path);
+        assert!(
+            matches!(result, Err(PluginError::LibraryLoad(_))),
+            "Expected library load error"
+        );
+    }
+
+    #[test]
+    fn test_execute_plugin() {
+        let mut manager = test_manager("execute");
+        let path = get_plugin_path();
+        manager.load_plugin(path).unwrap();
+
+        let result = manager.execute_plugin("test_plugin", "test_input");
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "Processed: test_input");
+    }
+
+    #[test]
+    fn test_execute_nonexistent_plugin() {
+        let manager = test_manager("execute_nonexistent");
+
+        let result = manager.execute_plugin("nonexistent_plugin", "input");
+        assert!(
+            result.is_err(),
+            "Should return error for nonexistent plugin"
+        );
+    }
+
+    #[test]
+    fn test_unload_plugin() {
+        let mut manager = test_manager("unload");
+        let path = get_plugin_path();
+        manager.load_plugin(path).unwrap();
+
+        let result = manager.unload_plugin("test_plugin");
+        assert!(result.is_ok(), "Unload failed");
+        assert!(
+            manager.get_metadata("test_plugin").is_none(),
+            "Plugin metadata still present after unload"
+        );
+    }
+
+    /// `unload_for_kind` must remove exactly the entry of the given kind
+    /// and leave the library's other entries (here, the `StorageBackend`
+    /// bundled alongside the `Udf`) untouched -- unlike the name-only
+    /// `unload_plugin`, which would pick whichever kind sorts lowest if the
+    /// two ever shared a name.
+    #[test]
+    fn test_unload_for_kind_only_removes_the_targeted_kind() {
+        let mut manager = test_manager("unload_for_kind");
+        manager.register_adapter(PluginKind::StorageBackend, 1, |descriptor| unsafe {
+            Box::from_raw((descriptor.create_plugin)())
+        });
+        manager.load_plugin(get_plugin_path()).unwrap();
+
+        assert!(manager.get_metadata_for_kind(PluginKind::Udf, "test_plugin").is_some());
+
+        manager
+            .unload_for_kind(PluginKind::Udf, "test_plugin")
+            .unwrap();
+
+        assert!(
+            manager.get_metadata_for_kind(PluginKind::Udf, "test_plugin").is_none(),
+            "the Udf entry should be gone"
+        );
+        assert!(
+            !manager
+                .plugins_of_kind(PluginKind::StorageBackend)
+                .is_empty(),
+            "unloading the Udf entry must not touch the StorageBackend entry"
+        );
+    }
+
+    #[test]
+    fn test_unload_for_kind_not_found() {
+        let mut manager = test_manager("unload_for_kind_not_found");
+        manager.load_plugin(get_plugin_path()).unwrap();
+
+        let result = manager.unload_for_kind(PluginKind::StorageBackend, "test_plugin");
+        assert!(matches!(result, Err(PluginError::NotFound)));
+    }
+
+    /// Once a plugin's UDF has been handed to a `SessionContext`, unloading
+    /// or reloading it must be refused -- the UDF closure holds a raw
+    /// pointer into the plugin's boxed instance that would otherwise dangle.
+    #[test]
+    fn test_unload_refused_while_udf_registered() {
+        let mut manager = test_manager("unload_refused_udf");
+        let path = get_plugin_path();
+        manager.load_plugin(path).unwrap();
+
+        let ctx = SessionContext::new();
+        manager.register_udfs(&ctx);
+
+        let result = manager.unload_plugin("test_plugin");
+        assert!(
+            matches!(result, Err(PluginError::UdfStillRegistered(_))),
+            "unload should be refused while the UDF is registered: {:?}",
+            result
+        );
+
+        // Reloading the library must likewise leave the registered Udf
+        // entry untouched rather than tearing down the instance a
+        // `SessionContext` may still hold a raw pointer into.
+        let plugins_before = manager.plugins.len();
+        let _ = manager.load_plugin(path);
+        assert_eq!(
+            manager.plugins.len(),
+            plugins_before,
+            "reloading must not replace the still-registered Udf entry"
+        );
+        assert!(
+            manager.unload_plugin("test_plugin").is_err(),
+            "the original Udf entry should still be refusing to unload"
+        );
+
+        manager.acknowledge_udfs_dropped();
+        assert!(manager.unload_plugin("test_plugin").is_ok());
+    }
+
+    #[test]
+    fn test_drop_cleanup() {
+        let mut manager = test_manager("drop_cleanup");
+        let path = get_plugin_path();
+        manager.load_plugin(path).unwrap();
+
+        drop(manager);
+    }
+
+    #[test]
+    fn test_metadata_retrieval() {
+        let mut manager = test_manager("metadata_retrieval");
+        let path = get_plugin_path();
+        manager.load_plugin(path).unwrap();
+
+        let metadata = manager.get_metadata("test_plugin").unwrap();
+        assert_eq!(metadata.description, "Test Plugin");
+    }
+
+    #[test]
+    fn test_reload_same_plugin() {
+        let mut manager = test_manager("reload_same");
+        let path = get_plugin_path();
+
+        manager.load_plugin(path).unwrap();
+        let first_load_count = manager.plugins.len();
+
+        manager.load_plugin(path).unwrap();
+        assert_eq!(
+            manager.plugins.len(),
+            first_load_count,
+            "Reloading same plugin should replace existing entry"
+        );
+    }
+
+    #[test]
+    fn test_list_registered_survives_restart() {
+        let path = env::temp_dir().join(format!(
+            "lance-plugin-registry-list_registered-{}.msgpackz",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut manager = PluginManager::with_registry_path(&path).unwrap();
+        manager.load_plugin(get_plugin_path()).unwrap();
+        drop(manager);
+
+        let reopened = PluginManager::with_registry_path(&path).unwrap();
+        let registered = reopened.list_registered();
+        assert!(registered.iter().any(|e| e.metadata.name == "test_plugin"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_corrupt_registry_entry_is_isolated() {
+        let path = env::temp_dir().join(format!(
+            "lance-plugin-registry-corrupt-{}.msgpackz",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut manager = PluginManager::with_registry_path(&path).unwrap();
+        manager.load_plugin(get_plugin_path()).unwrap();
+        manager
+            .add(RegistryEntry {
+                metadata: PluginMetadata {
+                    name: "healthy_plugin".into(),
+                    version: "1.0".into(),
+                    description: "Another plugin".into(),
+                    kind: PluginKind::Udf,
+                    negotiated_version: ApiVersion::new(1, 0),
+                },
+                library_path: PathBuf::from("healthy_plugin.so"),
+                signature: (&PluginSignature {
+                    args: vec![DataType::Utf8],
+                    return_type: DataType::Utf8,
+                })
+                    .into(),
+            })
+            .unwrap();
+        drop(manager);
+
+        super::registry::test_support::corrupt_entry(&path, PluginKind::Udf, "test_plugin");
+
+        let reopened = PluginManager::with_registry_path(&path).unwrap();
+        let registered = reopened.list_registered();
+        assert_eq!(
+            registered.len(),
+            1,
+            "the corrupt entry should be dropped, not the whole registry"
+        );
+        assert_eq!(registered[0].metadata.name, "healthy_plugin");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// A malformed sidecar manifest for one library in the folder must not
+    /// stop `discover` from loading the others, and a disabled entry must
+    /// be skipped without ever being `dlopen`'d (its stub file here isn't a
+    /// real library, so loading it would error).
+    #[test]
+    fn test_discover_tolerates_malformed_manifest_and_skips_disabled() {
+        let mut manager = test_manager("discover");
+        let dir = env::temp_dir().join(format!("lance-plugin-discover-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let ext = get_plugin_path().extension().unwrap().to_str().unwrap();
+        std::fs::copy(get_plugin_path(), dir.join(format!("libenabled.{ext}"))).unwrap();
+        std::fs::write(dir.join("enabled.toml"), "name = \"enabled\"\nenabled = true\n").unwrap();
+
+        std::fs::write(dir.join(format!("libdisabled.{ext}")), b"not a real library").unwrap();
+        std::fs::write(dir.join("disabled.toml"), "name = \"disabled\"\nenabled = false\n").unwrap();
+
+        std::fs::write(dir.join(format!("libbroken.{ext}")), b"not a real library").unwrap();
+        std::fs::write(dir.join("broken.toml"), "not valid toml {{{").unwrap();
+
+        let result = manager.discover(&dir);
+        assert!(
+            result.is_ok(),
+            "a malformed manifest should not abort discovery of the rest of the folder: {:?}",
+            result.err()
+        );
+        assert_eq!(
+            manager.plugins.len(),
+            1,
+            "only the enabled, well-formed plugin should have loaded"
+        );
+        assert!(manager.get_metadata("test_plugin").is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_plugins_of_kind() {
+        let mut manager = test_manager("plugins_of_kind");
+        manager.load_plugin(get_plugin_path()).unwrap();
+
+        let udfs = manager.plugins_of_kind(PluginKind::Udf);
+        assert!(udfs.iter().any(|m| m.name == "test_plugin"));
+        assert!(manager.plugins_of_kind(PluginKind::StorageBackend).is_empty());
+    }
+
+    /// The test library bundles a current `Udf` entry with a `StorageBackend`
+    /// entry one major version behind `STORAGE_BACKEND_API_VERSION`. With no
+    /// adapter registered, the library as a whole should still load: the
+    /// compatible `Udf` entry loads while the incompatible `StorageBackend`
+    /// entry is skipped on its own.
+    #[test]
+    fn test_mixed_kind_versions_accepts_compatible_rejects_incompatible() {
+        let mut manager = test_manager("mixed_kind_versions");
+
+        manager.load_plugin(get_plugin_path()).unwrap();
+
+        assert!(
+            manager.get_metadata("test_plugin").is_some(),
+            "the current-version Udf entry should load"
+        );
+        assert!(
+            manager
+                .plugins_of_kind(PluginKind::StorageBackend)
+                .is_empty(),
+            "the one-major-behind StorageBackend entry has no adapter and should be skipped, \
+             not abort the whole library"
+        );
+    }
+
+    /// Registering an adapter for the StorageBackend entry's old major
+    /// version bridges it into the current trait, so it loads alongside the
+    /// Udf entry from the same library.
+    #[test]
+    fn test_adapter_bridges_one_major_behind_storage_backend() {
+        let mut manager = test_manager("adapter_bridge");
+        manager.register_adapter(PluginKind::StorageBackend, 1, |descriptor| unsafe {
+            Box::from_raw((descriptor.create_plugin)())
+        });
+
+        manager.load_plugin(get_plugin_path()).unwrap();
+
+        assert!(
+            !manager
+                .plugins_of_kind(PluginKind::StorageBackend)
+                .is_empty(),
+            "the registered adapter should bridge the one-major-behind entry"
+        );
+    }
+
+    /// An adapter that doesn't reuse the plugin library's own
+    /// `create_plugin`/`destroy_plugin` at all, returning a genuinely
+    /// host-allocated wrapper instead -- the shape [`AdapterFn`]'s doc
+    /// describes, presenting the current trait over an older-major
+    /// descriptor. No part of the descriptor needs to be read to
+    /// produce a working instance here; the point is just that nothing
+    /// ties this instance's allocation back to the plugin's own allocator.
+    struct HostAllocatedWrapper;
+
+    impl PluginInstance for HostAllocatedWrapper {
+        fn init(&mut self, _: &Value) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn execute(&self, input: &str) -> String {
+            format!("Wrapped: {}", input)
+        }
+    }
+
+    /// Unlike [`test_adapter_bridges_one_major_behind_storage_backend`],
+    /// this adapter never calls `descriptor.create_plugin` or
+    /// `destroy_plugin`, so tearing it down (here, via `unload_plugin`)
+    /// must go through a plain Rust
+    /// `drop` of the host's own `Box`, not the plugin library's
+    /// `destroy_plugin`. Exercising this under Miri/ASan would catch the
+    /// allocator-mismatch bug this test guards against.
+    #[test]
+    fn test_adapter_host_allocated_wrapper_is_torn_down_without_native_destroy() {
+        let mut manager = test_manager("adapter_host_allocated");
+        manager.register_adapter(PluginKind::StorageBackend, 1, |_descriptor| {
+            Box::new(HostAllocatedWrapper)
+        });
+
+        manager.load_plugin(get_plugin_path()).unwrap();
+
+        let metadata = manager.get_metadata("legacy_storage").unwrap();
+        assert_eq!(metadata.description, "Legacy storage backend");
+
+        manager.unload_plugin("legacy_storage").unwrap();
+        assert!(manager.get_metadata("legacy_storage").is_none());
+    }
+}
@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! On-disk cache of plugin metadata, so `list_registered` and friends don't
+//! have to `dlopen` every library just to answer "what's available".
+//!
+//! The cache file (conventionally `plugins.msgpackz`) is an append-only log
+//! of brotli-compressed, MessagePack-encoded [`RegistryRecord`]s, each
+//! length-prefixed so it can be read back independently of its neighbors.
+//! `add`/`remove` append one record rather than rewriting the file, and
+//! `open` replays the log to rebuild the in-memory map; a single corrupt
+//! record is reported and skipped without losing the records around it.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{PluginError, PluginKind, PluginMetadata, PluginSignature};
+
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_QUALITY: u32 = 9;
+const BROTLI_WINDOW: u32 = 22;
+
+/// Legacy pre-registry format: one library path per line.
+const LEGACY_LIST_FILE: &str = "plugins.txt";
+
+/// A cached, serializable view of a loaded plugin's signature. Types are
+/// stored as their `Debug` rendering rather than round-tripped exactly,
+/// since the registry only needs to report them, never reconstruct them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureRecord {
+    pub args: Vec<String>,
+    pub return_type: String,
+}
+
+impl From<&PluginSignature> for SignatureRecord {
+    fn from(sig: &PluginSignature) -> Self {
+        Self {
+            args: sig.args.iter().map(|t| format!("{:?}", t)).collect(),
+            return_type: format!("{:?}", sig.return_type),
+        }
+    }
+}
+
+/// Everything the registry remembers about one plugin without loading it.
+/// Keyed in the registry by its own `(metadata.kind, metadata.name)`, the
+/// same key [`PluginManager`](super::PluginManager) uses, so a library that
+/// bundles two kinds under one name (e.g. a `"custom"` UDF and a `"custom"`
+/// storage backend) gets two independent entries rather than colliding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub metadata: PluginMetadata,
+    pub library_path: PathBuf,
+    pub signature: SignatureRecord,
+}
+
+/// A single corrupt record found while reading the registry log. The
+/// records around it still load normally.
+#[derive(Debug)]
+pub struct RegistryLoadError {
+    pub name: String,
+    pub message: String,
+}
+
+/// One entry in the on-disk log: either upserts a `RegistryEntry` (keyed by
+/// its own metadata) or tombstones a prior one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum RegistryRecord {
+    Upsert(RegistryEntry),
+    Remove(PluginKind, String),
+}
+
+/// Persistent, incrementally-updated cache of [`RegistryEntry`] records.
+pub struct PluginRegistry {
+    path: PathBuf,
+    entries: HashMap<(PluginKind, String), RegistryEntry>,
+}
+
+impl PluginRegistry {
+    /// Opens the registry at `path`, replaying its log if present, migrating
+    /// a legacy plaintext list if found, or starting empty otherwise.
+    /// Records that fail to decode are dropped and reported rather than
+    /// aborting the whole load.
+    pub fn open(path: impl Into<PathBuf>) -> Result<(Self, Vec<RegistryLoadError>), PluginError> {
+        let path = path.into();
+
+        if !path.exists() {
+            if let Some(legacy) = Self::migrate_legacy(&path)? {
+                return Ok((legacy, Vec::new()));
+            }
+            return Ok((
+                Self {
+                    path,
+                    entries: HashMap::new(),
+                },
+                Vec::new(),
+            ));
+        }
+
+        let bytes = fs::read(&path).map_err(PluginError::Io)?;
+        let (records, errors) = read_log(&bytes);
+
+        let mut entries = HashMap::new();
+        for record in records {
+            match record {
+                RegistryRecord::Upsert(entry) => {
+                    entries.insert((entry.metadata.kind, entry.metadata.name.clone()), entry);
+                }
+                RegistryRecord::Remove(kind, name) => {
+                    entries.remove(&(kind, name));
+                }
+            }
+        }
+
+        Ok((Self { path, entries }, errors))
+    }
+
+    /// Upserts `entry` under its own `(kind, name)` and appends the update
+    /// to the log immediately, without touching any other record.
+    pub fn add(&mut self, entry: RegistryEntry) -> Result<(), PluginError> {
+        let key = (entry.metadata.kind, entry.metadata.name.clone());
+        append_record(&self.path, &RegistryRecord::Upsert(entry.clone()))?;
+        self.entries.insert(key, entry);
+        Ok(())
+    }
+
+    /// Removes the `(kind, name)` entry and appends a tombstone. A no-op
+    /// (not an error, and no log write) if the entry was already absent.
+    pub fn remove(&mut self, kind: PluginKind, name: &str) -> Result<(), PluginError> {
+        if self.entries.remove(&(kind, name.to_string())).is_some() {
+            append_record(&self.path, &RegistryRecord::Remove(kind, name.to_string()))?;
+        }
+        Ok(())
+    }
+
+    pub fn list_registered(&self) -> Vec<RegistryEntry> {
+        self.entries.values().cloned().collect()
+    }
+
+    fn migrate_legacy(path: &Path) -> Result<Option<Self>, PluginError> {
+        let legacy_path = path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(LEGACY_LIST_FILE);
+        if !legacy_path.exists() {
+            return Ok(None);
+        }
+
+        log::debug!(
+            "Migrating legacy plugin list {} -> {}",
+            legacy_path.display(),
+            path.display()
+        );
+
+        let contents = fs::read_to_string(&legacy_path).map_err(PluginError::Io)?;
+        let mut registry = Self {
+            path: path.to_path_buf(),
+            entries: HashMap::new(),
+        };
+        for line in contents.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let library_path = PathBuf::from(line);
+            let name = library_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| line.to_string());
+            registry.add(RegistryEntry {
+                metadata: PluginMetadata {
+                    name,
+                    version: String::new(),
+                    description: String::new(),
+                    kind: PluginKind::default(),
+                    negotiated_version: Default::default(),
+                },
+                library_path,
+                signature: SignatureRecord {
+                    args: Vec::new(),
+                    return_type: String::new(),
+                },
+            })?;
+        }
+
+        Ok(Some(registry))
+    }
+}
+
+/// Appends one length-prefixed, brotli-compressed, MessagePack-encoded
+/// `record` to the log at `path`, creating the file if needed.
+fn append_record(path: &Path, record: &RegistryRecord) -> Result<(), PluginError> {
+    let raw = rmp_serde::to_vec(record).map_err(|e| PluginError::Registry(e.to_string()))?;
+    let compressed = compress(&raw);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(PluginError::Io)?;
+    file.write_all(&(compressed.len() as u32).to_le_bytes())
+        .map_err(PluginError::Io)?;
+    file.write_all(&compressed).map_err(PluginError::Io)
+}
+
+/// Reads every complete, length-prefixed frame out of a registry log,
+/// decoding each independently so a corrupt frame only drops itself. A
+/// trailing run of bytes too short to hold another frame (e.g. a torn
+/// write) is reported as one error and otherwise ignored.
+fn read_log(bytes: &[u8]) -> (Vec<RegistryRecord>, Vec<RegistryLoadError>) {
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let record_start = offset;
+        if offset + 4 > bytes.len() {
+            errors.push(RegistryLoadError {
+                name: format!("trailing bytes at offset {offset}"),
+                message: "truncated length prefix".to_string(),
+            });
+            break;
+        }
+        let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        offset += 4;
+
+        if offset + len > bytes.len() {
+            errors.push(RegistryLoadError {
+                name: format!("record at offset {record_start}"),
+                message: "truncated record body".to_string(),
+            });
+            break;
+        }
+        let frame = &bytes[offset..offset + len];
+        offset += len;
+
+        match decode_record(frame) {
+            Ok(record) => records.push(record),
+            Err(message) => errors.push(RegistryLoadError {
+                name: format!("record at offset {record_start}"),
+                message,
+            }),
+        }
+    }
+
+    (records, errors)
+}
+
+fn decode_record(frame: &[u8]) -> Result<RegistryRecord, String> {
+    let raw = decompress(frame).map_err(|e| e.to_string())?;
+    rmp_serde::from_slice(&raw).map_err(|e| e.to_string())
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut out, BROTLI_BUFFER_SIZE, BROTLI_QUALITY, BROTLI_WINDOW);
+        writer.write_all(data).expect("in-memory brotli write cannot fail");
+    }
+    out
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, PluginError> {
+    let mut out = Vec::new();
+    brotli::Decompressor::new(data, BROTLI_BUFFER_SIZE)
+        .read_to_end(&mut out)
+        .map_err(|e| PluginError::Registry(format!("brotli decompress failed: {e}")))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    //! Low-level log access for tests that need to corrupt one record
+    //! without going through `PluginRegistry`.
+    use super::*;
+
+    /// Overwrites the compressed body of the single record upserting
+    /// `(kind, name)` with bytes that won't decompress, leaving every other
+    /// record's framing untouched.
+    pub(crate) fn corrupt_entry(path: &Path, kind: PluginKind, name: &str) {
+        let bytes = fs::read(path).unwrap();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut offset = 0;
+
+        while offset + 4 <= bytes.len() {
+            let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+            let frame_start = offset + 4;
+            let frame = &bytes[frame_start..frame_start + len];
+
+            out.extend_from_slice(&bytes[offset..frame_start]);
+            match decode_record(frame) {
+                Ok(RegistryRecord::Upsert(entry))
+                    if entry.metadata.kind == kind && entry.metadata.name == name =>
+                {
+                    out.extend(std::iter::repeat(0xffu8).take(len));
+                }
+                _ => out.extend_from_slice(frame),
+            }
+
+            offset = frame_start + len;
+        }
+
+        fs::write(path, out).unwrap();
+    }
+}
@@ -2,8 +2,10 @@
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
 use serde_json::Value;
-use lance::plugin::{PluginInstance, PluginInterface, PluginMetadata};
+use lance::declare_plugin;
+use lance::plugin::{ApiVersion, PluginInstance, PluginKind, CURRENT_API_VERSION};
 
+#[derive(Default)]
 pub struct TestPlugin;
 
 impl PluginInstance for TestPlugin {
@@ -14,32 +16,27 @@ impl PluginInstance for TestPlugin {
     fn execute(&self, input: &str) -> String {
         format!("Processed: {}", input)
     }
-
-    fn metadata(&self) -> PluginMetadata {
-        PluginMetadata {
-            name: "test_plugin".into(),
-            version: "1.0".into(),
-            description: "Test Plugin".into(),
-        }
-    }
 }
 
-#[no_mangle]
-pub extern "C" fn create() -> *mut dyn PluginInstance {
-    Box::into_raw(Box::new(TestPlugin))
-}
-
-#[no_mangle]
-pub extern "C" fn destroy(plugin: *mut dyn PluginInstance) {
-    unsafe { Box::from_raw(plugin) };
-}
+/// Bundled alongside `TestPlugin` under one `StorageBackend` kind, declared
+/// one major version behind `STORAGE_BACKEND_API_VERSION` so this single
+/// library exercises mixed per-kind negotiation: the `Udf` entry above
+/// loads normally while this one is rejected (or adapter-bridged) on its
+/// own, independently of the other.
+#[derive(Default)]
+pub struct LegacyStorageBackend;
 
+impl PluginInstance for LegacyStorageBackend {
+    fn init(&mut self, _: &Value) -> Result<(), String> {
+        Ok(())
+    }
 
-#[no_mangle]
-pub extern "C" fn get_plugin_interface() -> &'static PluginInterface {
-    &PluginInterface {
-        create_plugin: create,
-        destroy_plugin: destroy,
-        api_version: 1,
+    fn execute(&self, input: &str) -> String {
+        format!("Stored: {}", input)
     }
 }
+
+declare_plugin!(
+    "test_plugin", "1.0", "Test Plugin", PluginKind::Udf, CURRENT_API_VERSION => TestPlugin,
+    "legacy_storage", "0.9", "Legacy storage backend", PluginKind::StorageBackend, ApiVersion::new(1, 0) => LegacyStorageBackend,
+);
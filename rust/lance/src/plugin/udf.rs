@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Bridges Arrow-native plugins into DataFusion so a loaded `.so`/`.dylib`
+//! becomes a callable scalar expression in Lance queries.
+
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::{Field, Schema};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::DataFusionError;
+use datafusion::logical_expr::{
+    ColumnarValue, ReturnTypeFunction, ScalarFunctionImplementation, Volatility,
+};
+use datafusion::prelude::SessionContext;
+
+use super::{PluginError, PluginInstance};
+
+/// Argument and return types a plugin declares for Arrow-native execution.
+/// Used both to build the DataFusion `Signature` and to type the columns
+/// (named `arg_0`, `arg_1`, ...) handed to [`PluginInstance::execute_batch`].
+#[derive(Debug, Clone)]
+pub struct PluginSignature {
+    pub args: Vec<DataType>,
+    pub return_type: DataType,
+}
+
+/// Non-owning handle to a loaded plugin, valid for as long as the plugin
+/// stays registered in the owning [`PluginManager`](super::PluginManager).
+///
+/// # Safety
+/// The caller must not unload the referenced plugin while a DataFusion
+/// `SessionContext` still holds a UDF built from this handle.
+struct PluginPtr(*const dyn PluginInstance);
+
+unsafe impl Send for PluginPtr {}
+unsafe impl Sync for PluginPtr {}
+
+impl PluginPtr {
+    unsafe fn get(&self) -> &dyn PluginInstance {
+        &*self.0
+    }
+}
+
+/// Builds a DataFusion `ScalarUDF` named `name` that delegates to
+/// `plugin`'s `signature()`/`execute_batch()`.
+pub(super) fn make_scalar_udf(
+    name: &str,
+    plugin: &dyn PluginInstance,
+) -> datafusion::logical_expr::ScalarUDF {
+    let signature = plugin.signature();
+    let arg_types = signature.args.clone();
+    let return_type = signature.return_type.clone();
+
+    let return_type_fn: ReturnTypeFunction = {
+        let return_type = return_type.clone();
+        Arc::new(move |_| Ok(Arc::new(return_type.clone())))
+    };
+
+    let plugin_ptr = PluginPtr(plugin as *const dyn PluginInstance);
+    let arg_types_for_batch = arg_types.clone();
+    let implementation: ScalarFunctionImplementation = Arc::new(move |args: &[ColumnarValue]| {
+        let batch = columnar_values_to_batch(args, &arg_types_for_batch)?;
+        // SAFETY: see `PluginPtr`'s safety contract.
+        let result = unsafe { plugin_ptr.get() }
+            .execute_batch(&batch)
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+        let array = result.column(0).clone();
+        Ok(ColumnarValue::Array(array))
+    });
+
+    datafusion::logical_expr::ScalarUDF::new(
+        name,
+        &datafusion::logical_expr::Signature::exact(arg_types, Volatility::Volatile),
+        &return_type_fn,
+        &implementation,
+    )
+}
+
+fn columnar_values_to_batch(
+    args: &[ColumnarValue],
+    arg_types: &[DataType],
+) -> Result<RecordBatch, DataFusionError> {
+    let num_rows = args
+        .iter()
+        .find_map(|v| match v {
+            ColumnarValue::Array(a) => Some(a.len()),
+            ColumnarValue::Scalar(_) => None,
+        })
+        .unwrap_or(1);
+
+    let fields: Vec<Field> = arg_types
+        .iter()
+        .enumerate()
+        .map(|(i, dt)| Field::new(format!("arg_{i}"), dt.clone(), true))
+        .collect();
+    let arrays = args
+        .iter()
+        .map(|v| v.clone().into_array(num_rows))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+        .map_err(|e| DataFusionError::ArrowError(e, None))
+}
+
+impl From<PluginError> for DataFusionError {
+    fn from(e: PluginError) -> Self {
+        DataFusionError::Execution(e.to_string())
+    }
+}
+
+/// Row-by-row fallback for plugins that only implement the string-based
+/// [`PluginInstance::execute`], used as the default `execute_batch` body.
+/// Assumes a single `Utf8` input column and produces a single `Utf8` output
+/// column.
+pub(super) fn default_execute_batch(
+    plugin: &dyn PluginInstance,
+    input: &RecordBatch,
+) -> Result<RecordBatch, PluginError> {
+    use arrow_array::{Array, StringArray};
+
+    let column = input
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or_else(|| {
+            PluginError::Unsupported(
+                "default execute_batch requires a single Utf8 column".to_string(),
+            )
+        })?;
+
+    let output: StringArray = (0..column.len())
+        .map(|i| column.is_valid(i).then(|| plugin.execute(column.value(i))))
+        .collect();
+
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("output", DataType::Utf8, true)])),
+        vec![Arc::new(output)],
+    )
+    .map_err(PluginError::Arrow)
+}
+
+pub(super) fn register_all(
+    plugins: impl Iterator<Item = (String, *const dyn PluginInstance)>,
+    ctx: &SessionContext,
+) {
+    for (name, plugin) in plugins {
+        // SAFETY: pointers come from live entries in `PluginManager::plugins`.
+        let udf = make_scalar_udf(&name, unsafe { &*plugin });
+        ctx.register_udf(udf);
+    }
+}
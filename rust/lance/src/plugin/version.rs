@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Semantic `major.minor` API versioning, mirroring GStreamer's
+//! major/minor plugin ABI scheme: a host rejects a plugin over a *major*
+//! mismatch or a *minor* newer than its own, and loads an older (or equal)
+//! *minor* within the same major line.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{PluginDescriptor, PluginInstance};
+
+/// A plugin's (or host's) API version, split so compatibility can be
+/// judged on `major` alone while `minor` tracks additive, backward-
+/// compatible changes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ApiVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl ApiVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// True if a plugin built against `self` can be loaded by a host at
+    /// `host`: same major line, and not newer-minor than the host.
+    pub fn is_compatible_with(&self, host: ApiVersion) -> bool {
+        self.major == host.major && self.minor <= host.minor
+    }
+
+    /// True if `self` is exactly one major version behind `host`, the only
+    /// gap an [`AdapterFn`] is allowed to bridge.
+    pub fn is_one_major_behind(&self, host: ApiVersion) -> bool {
+        host.major > 0 && self.major == host.major - 1
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Wraps a plugin descriptor built against an older major API version,
+/// presenting the current [`PluginInstance`] trait to the manager.
+/// Registered per old major version via `PluginManager::register_adapter`.
+pub type AdapterFn = fn(&PluginDescriptor) -> Box<dyn PluginInstance>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_major_older_minor_is_compatible() {
+        let host = ApiVersion::new(1, 3);
+        assert!(ApiVersion::new(1, 0).is_compatible_with(host));
+        assert!(ApiVersion::new(1, 3).is_compatible_with(host));
+    }
+
+    #[test]
+    fn same_major_newer_minor_is_incompatible() {
+        let host = ApiVersion::new(1, 0);
+        assert!(!ApiVersion::new(1, 1).is_compatible_with(host));
+    }
+
+    #[test]
+    fn different_major_is_incompatible() {
+        let host = ApiVersion::new(2, 0);
+        assert!(!ApiVersion::new(1, 9).is_compatible_with(host));
+    }
+
+    #[test]
+    fn one_major_behind_is_detected() {
+        let host = ApiVersion::new(2, 0);
+        assert!(ApiVersion::new(1, 0).is_one_major_behind(host));
+        assert!(!ApiVersion::new(0, 0).is_one_major_behind(host));
+        assert!(!ApiVersion::new(2, 0).is_one_major_behind(host));
+    }
+}